@@ -1,18 +1,46 @@
-use enigo::{Enigo, Key, Keyboard, Settings, Direction};
+use crate::clipboard::ClipboardProvider;
+use crate::config::ConfigStore;
+use crate::injection::{
+    backspace_n, inject_by_paste, inject_by_typing, resolve_mode, InjectionMode,
+    DEFAULT_PASTE_DELAY_MS,
+};
+use crate::template::{render, ExpandContext};
+use enigo::{Direction, Enigo, Key, Keyboard, Settings};
 use rdev::{listen, Event, EventType, Key as RKey};
+use regex::Regex;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::thread;
 
+/// A regex-triggered snippet: its pattern, template, and whether it requires
+/// a preceding word boundary to fire (so e.g. `in` doesn't match inside
+/// `working`).
+struct RegexTrigger {
+    pattern: Regex,
+    template: String,
+    word: bool,
+}
+
+/// A resolved trigger match, ready to render: which template to expand,
+/// what its regex capture groups were (empty for exact matches), and how
+/// many characters of the buffer it consumed (and so need erasing).
+pub struct Trigger {
+    pub template: String,
+    pub captures: Vec<String>,
+    pub consumed: usize,
+}
+
 #[derive(Clone)]
 pub struct SnippetMap {
     snippets: Arc<Mutex<HashMap<String, String>>>,
+    regex_triggers: Arc<Mutex<Vec<RegexTrigger>>>,
 }
 
 impl SnippetMap {
     pub fn new() -> Self {
         Self {
             snippets: Arc::new(Mutex::new(HashMap::new())),
+            regex_triggers: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
@@ -25,9 +53,106 @@ impl SnippetMap {
         tracing::info!("Updated snippet map with {} snippets", map.len());
     }
 
+    /// Compiles and installs the regex-triggered snippets, replacing any
+    /// previous set. Rejects the whole batch on the first invalid pattern
+    /// rather than installing a partial, confusing set of triggers.
+    pub fn update_regex(&self, patterns: Vec<(String, String, bool)>) -> Result<(), String> {
+        let mut compiled = Vec::with_capacity(patterns.len());
+        for (pattern, template, word) in patterns {
+            let regex = Regex::new(&pattern)
+                .map_err(|e| format!("Invalid regex trigger '{}': {}", pattern, e))?;
+            compiled.push(RegexTrigger {
+                pattern: regex,
+                template,
+                word,
+            });
+        }
+
+        tracing::info!(
+            "Updated regex snippet triggers with {} patterns",
+            compiled.len()
+        );
+        *self.regex_triggers.lock().unwrap() = compiled;
+        Ok(())
+    }
+
     pub fn get(&self, keyword: &str) -> Option<String> {
         self.snippets.lock().unwrap().get(keyword).cloned()
     }
+
+    /// Returns a snapshot of every `(keyword, template)` pair currently
+    /// held, for consumers like the fuzzy picker that need to search across
+    /// all snippets rather than look one up by exact keyword.
+    pub fn entries(&self) -> Vec<(String, String)> {
+        self.snippets
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+
+    /// Resolves `buffer` against the exact keyword map first, then against
+    /// each regex trigger in turn, requiring the regex to match the tail of
+    /// the buffer (so triggers complete as you finish typing them).
+    pub fn resolve(&self, buffer: &str) -> Option<Trigger> {
+        let trimmed = buffer.trim();
+        if let Some(template) = self.get(trimmed) {
+            return Some(Trigger {
+                template,
+                captures: Vec::new(),
+                consumed: trimmed.chars().count(),
+            });
+        }
+
+        let regex_triggers = self.regex_triggers.lock().unwrap();
+        for trigger in regex_triggers.iter() {
+            let Some(caps) = trigger
+                .pattern
+                .captures_iter(buffer)
+                .find(|caps| caps.get(0).map_or(false, |m| m.end() == buffer.len()))
+            else {
+                continue;
+            };
+            let matched = caps.get(0).unwrap();
+
+            if trigger.word {
+                let preceding = buffer[..matched.start()].chars().next_back();
+                let at_boundary = preceding.map_or(true, |c| !c.is_alphanumeric());
+                if !at_boundary {
+                    continue;
+                }
+            }
+
+            let captures = caps
+                .iter()
+                .skip(1)
+                .map(|group| group.map(|g| g.as_str().to_string()).unwrap_or_default())
+                .collect();
+
+            return Some(Trigger {
+                template: trigger.template.clone(),
+                captures,
+                consumed: matched.as_str().chars().count(),
+            });
+        }
+
+        None
+    }
+}
+
+/// Tracks the shift/caps-lock state needed to turn a raw key event into the
+/// character it actually produces.
+#[derive(Clone, Copy, Default)]
+struct ModifierState {
+    shift: bool,
+    caps_lock: bool,
+}
+
+impl ModifierState {
+    fn uppercase(&self) -> bool {
+        self.shift ^ self.caps_lock
+    }
 }
 
 #[derive(Clone)]
@@ -35,30 +160,97 @@ pub struct AutoExpander {
     buffer: Arc<Mutex<String>>,
     snippet_map: SnippetMap,
     enabled: Arc<Mutex<bool>>,
+    injection_mode: Arc<Mutex<InjectionMode>>,
+    config: ConfigStore,
+    clipboard: Arc<dyn ClipboardProvider>,
 }
 
 impl AutoExpander {
-    pub fn new(snippet_map: SnippetMap) -> Self {
+    pub fn new(
+        snippet_map: SnippetMap,
+        config: ConfigStore,
+        clipboard: Arc<dyn ClipboardProvider>,
+    ) -> Self {
         Self {
             buffer: Arc::new(Mutex::new(String::new())),
             snippet_map,
             enabled: Arc::new(Mutex::new(false)),
+            injection_mode: Arc::new(Mutex::new(InjectionMode::Type)),
+            config,
+            clipboard,
         }
     }
 
     pub fn set_enabled(&self, enabled: bool) {
         *self.enabled.lock().unwrap() = enabled;
-        tracing::info!("Auto-expansion {}", if enabled { "enabled" } else { "disabled" });
+        tracing::info!(
+            "Auto-expansion {}",
+            if enabled { "enabled" } else { "disabled" }
+        );
     }
 
     pub fn is_enabled(&self) -> bool {
         *self.enabled.lock().unwrap()
     }
 
+    pub fn set_injection_mode(&self, mode: InjectionMode) {
+        *self.injection_mode.lock().unwrap() = mode;
+        tracing::info!("Injection mode set to {:?}", mode);
+    }
+
+    /// Renders `keyword`'s template and injects it at the current cursor,
+    /// the same way auto-expansion would, without requiring the keyword to
+    /// have been typed first. Used by the snippet picker palette.
+    pub fn insert_snippet(&self, keyword: &str) -> Result<(), String> {
+        let template = self
+            .snippet_map
+            .get(keyword)
+            .ok_or_else(|| format!("Unknown snippet: {}", keyword))?;
+
+        let ctx = ExpandContext {
+            snippet_map: self.snippet_map.clone(),
+            captures: Vec::new(),
+            clipboard: Arc::clone(&self.clipboard),
+        };
+        let expansion = render(&template, &ctx);
+
+        let settings = Settings::default();
+        let mut enigo = Enigo::new(&settings).map_err(|e| e.to_string())?;
+
+        let preference = *self.injection_mode.lock().unwrap();
+        let paste_delay_ms = self
+            .config
+            .get()
+            .global
+            .paste_delay_ms
+            .unwrap_or(DEFAULT_PASTE_DELAY_MS);
+        match resolve_mode(preference, expansion.text.chars().count()) {
+            InjectionMode::Type => inject_by_typing(&mut enigo, &expansion.text),
+            InjectionMode::Paste => inject_by_paste(
+                &mut enigo,
+                &expansion.text,
+                paste_delay_ms,
+                self.clipboard.as_ref(),
+            ),
+        }
+
+        if let Some(offset) = expansion.cursor_offset {
+            for _ in 0..offset {
+                enigo.key(Key::LeftArrow, Direction::Click).ok();
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn start(self) {
         let buffer = Arc::clone(&self.buffer);
         let snippet_map = self.snippet_map.clone();
         let enabled = Arc::clone(&self.enabled);
+        let injection_mode = Arc::clone(&self.injection_mode);
+        let config = self.config.clone();
+        let clipboard = Arc::clone(&self.clipboard);
+        let modifiers = Arc::new(Mutex::new(ModifierState::default()));
 
         thread::spawn(move || {
             tracing::info!("Starting keyboard listener thread");
@@ -69,37 +261,56 @@ impl AutoExpander {
                 }
 
                 match event.event_type {
+                    EventType::KeyRelease(RKey::ShiftLeft)
+                    | EventType::KeyRelease(RKey::ShiftRight) => {
+                        modifiers.lock().unwrap().shift = false;
+                    }
+                    EventType::KeyPress(RKey::ShiftLeft)
+                    | EventType::KeyPress(RKey::ShiftRight) => {
+                        modifiers.lock().unwrap().shift = true;
+                    }
+                    EventType::KeyPress(RKey::CapsLock) => {
+                        let mut modifiers = modifiers.lock().unwrap();
+                        modifiers.caps_lock = !modifiers.caps_lock;
+                    }
                     EventType::KeyPress(key) => {
                         let mut buf = buffer.lock().unwrap();
+                        let mods = *modifiers.lock().unwrap();
 
                         match key {
                             RKey::Space | RKey::Return | RKey::Tab => {
-                                // Check if buffer matches any keyword
-                                let keyword = buf.trim().to_string();
-                                tracing::debug!("Checking keyword: '{}'", keyword);
-
-                                if let Some(expansion) = snippet_map.get(&keyword) {
-                                    tracing::info!("Expanding keyword '{}' to: '{}'", keyword, expansion);
-
-                                    // Create enigo with default settings
-                                    let settings = Settings::default();
-                                    let mut enigo = match Enigo::new(&settings) {
-                                        Ok(enigo) => enigo,
-                                        Err(e) => {
-                                            tracing::error!("Failed to create Enigo: {:?}", e);
-                                            return;
-                                        }
-                                    };
+                                // Check if the buffer matches an exact keyword or a
+                                // regex trigger's tail. This is cheap and in-memory, so
+                                // it runs before the app-focus check below (which isn't).
+                                tracing::debug!("Checking buffer: '{}'", buf.trim());
 
-                                    // Erase the keyword (backspace n times)
-                                    for _ in 0..keyword.len() {
-                                        enigo.key(Key::Backspace, Direction::Click).ok();
-                                    }
+                                if let Some(trigger) = snippet_map.resolve(&buf) {
+                                    // Rendering and injection involve clipboard I/O,
+                                    // backspace/paste delays, and potentially shelling
+                                    // out (Wayland/X11 clipboard CLIs, and here also the
+                                    // active-app lookup) — run it off the `rdev` hook
+                                    // thread so a slow paste can't stall or get the hook
+                                    // throttled by the OS.
+                                    let snippet_map = snippet_map.clone();
+                                    let injection_mode = Arc::clone(&injection_mode);
+                                    let config = config.clone();
+                                    let clipboard = Arc::clone(&clipboard);
 
-                                    // Type the expansion
-                                    enigo.text(&expansion).ok();
+                                    thread::spawn(move || {
+                                        let active_app = crate::config::active_app_id();
+                                        let app_expansion_allowed =
+                                            config.get().is_enabled_for_app(active_app.as_deref());
 
-                                    tracing::info!("Expansion complete");
+                                        if app_expansion_allowed {
+                                            expand_trigger(
+                                                trigger,
+                                                snippet_map,
+                                                injection_mode,
+                                                config,
+                                                clipboard,
+                                            );
+                                        }
+                                    });
                                 }
 
                                 buf.clear();
@@ -108,8 +319,19 @@ impl AutoExpander {
                                 buf.pop();
                             }
                             _ => {
-                                // Try to convert key to char
-                                if let Some(ch) = key_to_char(key) {
+                                // Prefer the character the OS actually produced for this
+                                // key (accounts for layout, dead keys, etc.) and fall back
+                                // to the hardcoded US-QWERTY table otherwise.
+                                let produced = event
+                                    .name
+                                    .as_deref()
+                                    .and_then(|name| {
+                                        let mut chars = name.chars();
+                                        chars.next().filter(|_| chars.next().is_none())
+                                    })
+                                    .or_else(|| key_to_char(key, mods));
+
+                                if let Some(ch) = produced {
                                     buf.push(ch);
 
                                     // Limit buffer size to prevent memory issues
@@ -132,7 +354,102 @@ impl AutoExpander {
     }
 }
 
-fn key_to_char(key: RKey) -> Option<char> {
+/// Renders `trigger`'s template and injects it: erases whatever the trigger
+/// consumed, types or pastes the expansion, then walks the caret back to any
+/// `{{cursor}}` marker. Runs off the `rdev` hook thread (see `start` above),
+/// since paste/backspace delays and clipboard I/O can take hundreds of ms.
+fn expand_trigger(
+    trigger: Trigger,
+    snippet_map: SnippetMap,
+    injection_mode: Arc<Mutex<InjectionMode>>,
+    config: ConfigStore,
+    clipboard: Arc<dyn ClipboardProvider>,
+) {
+    let ctx = ExpandContext {
+        snippet_map,
+        captures: trigger.captures,
+        clipboard: Arc::clone(&clipboard),
+    };
+    let expansion = render(&trigger.template, &ctx);
+    tracing::info!("Expanding to: '{}'", expansion.text);
+
+    let settings = Settings::default();
+    let mut enigo = match Enigo::new(&settings) {
+        Ok(enigo) => enigo,
+        Err(e) => {
+            tracing::error!("Failed to create Enigo: {:?}", e);
+            return;
+        }
+    };
+
+    let global = config.get().global;
+
+    // Erase whatever the trigger matched (backspace n times)
+    backspace_n(
+        &mut enigo,
+        trigger.consumed,
+        global.backspace_delay_ms.unwrap_or(0),
+    );
+
+    // Inject the expansion via the configured backend, falling back to
+    // paste for long expansions.
+    let preference = *injection_mode.lock().unwrap();
+    match resolve_mode(preference, expansion.text.chars().count()) {
+        InjectionMode::Type => inject_by_typing(&mut enigo, &expansion.text),
+        InjectionMode::Paste => inject_by_paste(
+            &mut enigo,
+            &expansion.text,
+            global.paste_delay_ms.unwrap_or(DEFAULT_PASTE_DELAY_MS),
+            clipboard.as_ref(),
+        ),
+    }
+
+    // If the template had a {{cursor}} marker, walk the caret back to it
+    // with left-arrow presses.
+    if let Some(offset) = expansion.cursor_offset {
+        for _ in 0..offset {
+            enigo.key(Key::LeftArrow, Direction::Click).ok();
+        }
+    }
+
+    tracing::info!("Expansion complete");
+}
+
+/// Fallback US-QWERTY decoding used when `rdev` doesn't give us the actual
+/// character the OS produced for a key event.
+fn key_to_char(key: RKey, modifiers: ModifierState) -> Option<char> {
+    if let Some(letter) = letter_to_char(key) {
+        return Some(if modifiers.uppercase() {
+            letter.to_ascii_uppercase()
+        } else {
+            letter
+        });
+    }
+
+    if modifiers.shift {
+        if let Some(shifted) = shifted_symbol(key) {
+            return Some(shifted);
+        }
+    }
+
+    match key {
+        RKey::Num0 => Some('0'),
+        RKey::Num1 => Some('1'),
+        RKey::Num2 => Some('2'),
+        RKey::Num3 => Some('3'),
+        RKey::Num4 => Some('4'),
+        RKey::Num5 => Some('5'),
+        RKey::Num6 => Some('6'),
+        RKey::Num7 => Some('7'),
+        RKey::Num8 => Some('8'),
+        RKey::Num9 => Some('9'),
+        RKey::Minus => Some('-'),
+        RKey::Equal => Some('='),
+        _ => None,
+    }
+}
+
+fn letter_to_char(key: RKey) -> Option<char> {
     match key {
         RKey::KeyA => Some('a'),
         RKey::KeyB => Some('b'),
@@ -160,18 +477,25 @@ fn key_to_char(key: RKey) -> Option<char> {
         RKey::KeyX => Some('x'),
         RKey::KeyY => Some('y'),
         RKey::KeyZ => Some('z'),
-        RKey::Num0 => Some('0'),
-        RKey::Num1 => Some('1'),
-        RKey::Num2 => Some('2'),
-        RKey::Num3 => Some('3'),
-        RKey::Num4 => Some('4'),
-        RKey::Num5 => Some('5'),
-        RKey::Num6 => Some('6'),
-        RKey::Num7 => Some('7'),
-        RKey::Num8 => Some('8'),
-        RKey::Num9 => Some('9'),
-        RKey::Minus => Some('-'),
-        RKey::Equal => Some('='),
+        _ => None,
+    }
+}
+
+/// US-QWERTY shifted symbols for the number row and `-`/`=`.
+fn shifted_symbol(key: RKey) -> Option<char> {
+    match key {
+        RKey::Num1 => Some('!'),
+        RKey::Num2 => Some('@'),
+        RKey::Num3 => Some('#'),
+        RKey::Num4 => Some('$'),
+        RKey::Num5 => Some('%'),
+        RKey::Num6 => Some('^'),
+        RKey::Num7 => Some('&'),
+        RKey::Num8 => Some('*'),
+        RKey::Num9 => Some('('),
+        RKey::Num0 => Some(')'),
+        RKey::Minus => Some('_'),
+        RKey::Equal => Some('+'),
         _ => None,
     }
 }
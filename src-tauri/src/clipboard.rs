@@ -1,15 +1,227 @@
 use arboard::Clipboard;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+/// Abstracts over the OS clipboard so the rest of the app doesn't care
+/// whether we're talking to `arboard`, Wayland's `wl-copy`/`wl-paste`, or an
+/// X11 selection tool.
+pub trait ClipboardProvider: Send + Sync {
+    /// Reads the regular (`CLIPBOARD`) selection.
+    fn get(&self) -> Result<String, String>;
+    /// Writes the regular (`CLIPBOARD`) selection.
+    fn set(&self, text: String) -> Result<(), String>;
+
+    /// Reads the X11 `PRIMARY` selection (middle-click paste). Backends that
+    /// don't have a primary selection (the `arboard` backend used on macOS,
+    /// Windows and as the Linux fallback) report this as unsupported rather
+    /// than silently no-op-ing.
+    fn get_primary(&self) -> Result<String, String> {
+        Err("primary selection not supported on this backend".to_string())
+    }
+
+    /// Writes the X11 `PRIMARY` selection.
+    fn set_primary(&self, _text: String) -> Result<(), String> {
+        Err("primary selection not supported on this backend".to_string())
+    }
+}
+
+/// Default backend: the cross-platform `arboard` crate. Used on macOS and
+/// Windows, and as the Linux fallback when no clipboard CLI is found.
+struct ArboardProvider;
+
+impl ClipboardProvider for ArboardProvider {
+    fn get(&self) -> Result<String, String> {
+        Clipboard::new()
+            .and_then(|mut clipboard| clipboard.get_text())
+            .map_err(|e| e.to_string())
+    }
+
+    fn set(&self, text: String) -> Result<(), String> {
+        Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_text(text))
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Which Linux clipboard CLI to shell out to.
+#[cfg(target_os = "linux")]
+#[derive(Clone, Copy)]
+enum ShellTool {
+    /// `wl-copy` / `wl-paste` (Wayland).
+    Wayland,
+    /// `xclip` (X11).
+    Xclip,
+    /// `xsel` (X11).
+    Xsel,
+}
+
+#[cfg(target_os = "linux")]
+struct ShellClipboardProvider {
+    tool: ShellTool,
+}
+
+#[cfg(target_os = "linux")]
+impl ClipboardProvider for ShellClipboardProvider {
+    fn get(&self) -> Result<String, String> {
+        self.paste(false)
+    }
+
+    fn set(&self, text: String) -> Result<(), String> {
+        self.copy(text, false)
+    }
+
+    fn get_primary(&self) -> Result<String, String> {
+        match self.tool {
+            ShellTool::Wayland | ShellTool::Xclip | ShellTool::Xsel => self.paste(true),
+        }
+    }
+
+    fn set_primary(&self, text: String) -> Result<(), String> {
+        self.copy(text, true)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl ShellClipboardProvider {
+    fn copy(&self, text: String, primary: bool) -> Result<(), String> {
+        let (cmd, args) = match self.tool {
+            ShellTool::Wayland => ("wl-copy", primary.then_some("--primary")),
+            ShellTool::Xclip => ("xclip", None),
+            ShellTool::Xsel => ("xsel", None),
+        };
+
+        let mut args: Vec<&str> = args.into_iter().collect();
+        match self.tool {
+            ShellTool::Xclip => {
+                args.push("-selection");
+                args.push(if primary { "primary" } else { "clipboard" });
+            }
+            ShellTool::Xsel => {
+                args.push(if primary { "--primary" } else { "--clipboard" });
+                args.push("--input");
+            }
+            ShellTool::Wayland => {}
+        }
+
+        run_with_stdin(cmd, &args, &text)
+    }
+
+    fn paste(&self, primary: bool) -> Result<String, String> {
+        let (cmd, mut args): (&str, Vec<&str>) = match self.tool {
+            ShellTool::Wayland => ("wl-paste", vec!["--no-newline"]),
+            ShellTool::Xclip => ("xclip", vec!["-o"]),
+            ShellTool::Xsel => ("xsel", vec!["--output"]),
+        };
+
+        match self.tool {
+            ShellTool::Wayland if primary => args.push("--primary"),
+            ShellTool::Xclip => {
+                args.push("-selection");
+                args.push(if primary { "primary" } else { "clipboard" });
+            }
+            ShellTool::Xsel => args.push(if primary { "--primary" } else { "--clipboard" }),
+            _ => {}
+        }
+
+        run_capture_stdout(cmd, &args)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn run_with_stdin(cmd: &str, args: &[&str], input: &str) -> Result<(), String> {
+    let mut child = Command::new(cmd)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to spawn {}: {}", cmd, e))?;
+
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| format!("no stdin handle for {}", cmd))?
+        .write_all(input.as_bytes())
+        .map_err(|e| format!("failed to write to {}: {}", cmd, e))?;
+
+    child
+        .wait()
+        .map(|_| ())
+        .map_err(|e| format!("failed to wait for {}: {}", cmd, e))
+}
+
+#[cfg(target_os = "linux")]
+fn run_capture_stdout(cmd: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(cmd)
+        .args(args)
+        .output()
+        .map_err(|e| format!("failed to run {}: {}", cmd, e))?;
+
+    if !output.status.success() {
+        return Err(format!("{} exited with {}", cmd, output.status));
+    }
+
+    String::from_utf8(output.stdout).map_err(|e| e.to_string())
+}
+
+/// Checks whether `cmd` is on `PATH`, the way `which` would.
+#[cfg(target_os = "linux")]
+fn command_exists(cmd: &str) -> bool {
+    Command::new("which")
+        .arg(cmd)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// Detects the best clipboard backend for the current platform: on Linux,
+/// prefer Wayland's `wl-copy`/`wl-paste`, then `xclip`, then `xsel`, falling
+/// back to `arboard` if none of those CLIs are installed. macOS and Windows
+/// always use `arboard`.
+pub fn detect_provider() -> Arc<dyn ClipboardProvider> {
+    #[cfg(target_os = "linux")]
+    {
+        if command_exists("wl-copy") && command_exists("wl-paste") {
+            tracing::info!("Using wl-clipboard backend");
+            return Arc::new(ShellClipboardProvider {
+                tool: ShellTool::Wayland,
+            });
+        }
+        if command_exists("xclip") {
+            tracing::info!("Using xclip backend");
+            return Arc::new(ShellClipboardProvider {
+                tool: ShellTool::Xclip,
+            });
+        }
+        if command_exists("xsel") {
+            tracing::info!("Using xsel backend");
+            return Arc::new(ShellClipboardProvider {
+                tool: ShellTool::Xsel,
+            });
+        }
+        tracing::info!("No Wayland/X11 clipboard CLI found, falling back to arboard");
+    }
+
+    Arc::new(ArboardProvider)
+}
+
+#[tauri::command]
+pub fn read_clipboard(state: tauri::State<crate::AppState>) -> Result<String, String> {
+    state.clipboard.get()
+}
+
+#[tauri::command]
+pub fn write_clipboard(text: String, state: tauri::State<crate::AppState>) -> Result<(), String> {
+    state.clipboard.set(text)
+}
 
 #[tauri::command]
-pub fn read_clipboard() -> Result<String, String> {
-    Clipboard::new()
-        .and_then(|mut clipboard| clipboard.get_text())
-        .map_err(|e| e.to_string())
+pub fn read_primary(state: tauri::State<crate::AppState>) -> Result<String, String> {
+    state.clipboard.get_primary()
 }
 
 #[tauri::command]
-pub fn write_clipboard(text: String) -> Result<(), String> {
-    Clipboard::new()
-        .and_then(|mut clipboard| clipboard.set_text(text))
-        .map_err(|e| e.to_string())
+pub fn write_primary(text: String, state: tauri::State<crate::AppState>) -> Result<(), String> {
+    state.clipboard.set_primary(text)
 }
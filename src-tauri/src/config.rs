@@ -0,0 +1,228 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Global options that apply regardless of which application is focused.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct GlobalConfig {
+    /// `"type"` or `"paste"`. Leave unset to type expansions; either way,
+    /// expansions over the paste length threshold are pasted (see
+    /// `injection::resolve_mode`).
+    pub injection_mode: Option<String>,
+    /// How long to wait, after sending the paste shortcut, before restoring
+    /// the user's original clipboard contents.
+    pub paste_delay_ms: Option<u64>,
+    /// Key combo that opens the snippet picker palette, e.g. `"ctrl+shift+space"`.
+    pub toggle_hotkey: Option<String>,
+    /// Delay between each backspace keystroke when erasing a matched trigger.
+    pub backspace_delay_ms: Option<u64>,
+}
+
+/// Per-application override: enables or disables expansion while `app_id`
+/// (a macOS bundle id, or an X11 window class) is focused. The focused app
+/// is not detected on Windows, so rules never match there.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AppRule {
+    pub app_id: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct Config {
+    pub global: GlobalConfig,
+    pub apps: Vec<AppRule>,
+}
+
+impl Config {
+    fn parse(contents: &str) -> Result<Config, String> {
+        toml::from_str(contents).map_err(|e| format!("Failed to parse config.toml: {}", e))
+    }
+
+    /// Whether auto-expansion should run while `app_id` is focused. Apps
+    /// with no matching rule are allowed by default.
+    pub fn is_enabled_for_app(&self, app_id: Option<&str>) -> bool {
+        let Some(app_id) = app_id else {
+            return true;
+        };
+
+        self.apps
+            .iter()
+            .find(|rule| rule.app_id == app_id)
+            .map(|rule| rule.enabled)
+            .unwrap_or(true)
+    }
+}
+
+/// Holds the live config, reloadable from disk without restarting the app.
+#[derive(Clone)]
+pub struct ConfigStore {
+    path: PathBuf,
+    config: Arc<Mutex<Config>>,
+    /// Set when the initial `load()` found a `config.toml` it couldn't
+    /// read or parse, so the frontend can surface it (via `load_error`/the
+    /// `config_load_error` command) instead of the broken config silently
+    /// vanishing into the fallback defaults.
+    load_error: Arc<Mutex<Option<String>>>,
+}
+
+impl Default for ConfigStore {
+    /// Built-in defaults with no config file backing them, used when
+    /// `ConfigStore::load` fails and the app falls back to running
+    /// unconfigured rather than refusing to start.
+    fn default() -> Self {
+        Self {
+            path: Self::default_path(),
+            config: Arc::new(Mutex::new(Config::default())),
+            load_error: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl ConfigStore {
+    /// `<OS config dir>/snippet-app/config.toml`, e.g.
+    /// `~/.config/snippet-app/config.toml` on Linux.
+    pub fn default_path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("snippet-app")
+            .join("config.toml")
+    }
+
+    /// Loads `config.toml` from the OS config dir. A missing file is not an
+    /// error (the app runs with defaults); a malformed file falls back to
+    /// defaults too, rather than refusing to start, but the parse error is
+    /// kept around for `load_error` so the frontend can still surface it.
+    pub fn load() -> Self {
+        let path = Self::default_path();
+        match Self::read(&path) {
+            Ok(config) => Self {
+                path,
+                config: Arc::new(Mutex::new(config)),
+                load_error: Arc::new(Mutex::new(None)),
+            },
+            Err(e) => {
+                tracing::error!("{}; falling back to default config", e);
+                Self {
+                    path,
+                    config: Arc::new(Mutex::new(Config::default())),
+                    load_error: Arc::new(Mutex::new(Some(e))),
+                }
+            }
+        }
+    }
+
+    /// The error from the initial `load()`, if `config.toml` existed but
+    /// couldn't be read or parsed. `None` once `reload()` has succeeded since.
+    pub fn load_error(&self) -> Option<String> {
+        self.load_error.lock().unwrap().clone()
+    }
+
+    fn read(path: &Path) -> Result<Config, String> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Config::parse(&contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn get(&self) -> Config {
+        self.config.lock().unwrap().clone()
+    }
+
+    pub fn reload(&self) -> Result<(), String> {
+        let config = Self::read(&self.path)?;
+        *self.config.lock().unwrap() = config;
+        *self.load_error.lock().unwrap() = None;
+        tracing::info!("Reloaded config.toml");
+        Ok(())
+    }
+
+    /// Watches `config.toml` for changes and reloads it in the background so
+    /// edits take effect without restarting the app.
+    pub fn watch(&self) {
+        let store = self.clone();
+
+        thread::spawn(move || {
+            use notify::{RecursiveMode, Watcher};
+
+            let (tx, rx) = std::sync::mpsc::channel();
+            let mut watcher = match notify::recommended_watcher(move |res| {
+                let _ = tx.send(res);
+            }) {
+                Ok(watcher) => watcher,
+                Err(e) => {
+                    tracing::error!("Failed to create config file watcher: {:?}", e);
+                    return;
+                }
+            };
+
+            if let Some(parent) = store.path.parent() {
+                if let Err(e) = watcher.watch(parent, RecursiveMode::NonRecursive) {
+                    tracing::warn!("Could not watch config directory: {:?}", e);
+                    return;
+                }
+            }
+
+            for res in rx {
+                match res {
+                    Ok(event) if event.paths.iter().any(|p| p == &store.path) => {
+                        if let Err(e) = store.reload() {
+                            tracing::error!("{}", e);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Config watcher error: {:?}", e),
+                }
+            }
+        });
+    }
+}
+
+/// Detects the focused application's bundle id (macOS) or window class
+/// (Linux/X11), for matching against `AppRule::app_id`. Returns `None` when
+/// detection isn't supported or the lookup fails (e.g. on Wayland, where
+/// there's no portable way to ask for the focused window's class).
+pub fn active_app_id() -> Option<String> {
+    #[cfg(target_os = "macos")]
+    {
+        let output = Command::new("osascript")
+            .args([
+                "-e",
+                "tell application \"System Events\" to get bundle identifier of first application process whose frontmost is true",
+            ])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let id = String::from_utf8(output.stdout).ok()?.trim().to_string();
+        return (!id.is_empty()).then_some(id);
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let output = Command::new("xdotool")
+            .args(["getactivewindow", "getwindowclassname"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let id = String::from_utf8(output.stdout).ok()?.trim().to_string();
+        return (!id.is_empty()).then_some(id);
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        None
+    }
+}
@@ -0,0 +1,100 @@
+use crate::clipboard::ClipboardProvider;
+use enigo::{Direction, Enigo, Key, Keyboard};
+use std::thread;
+use std::time::Duration;
+
+/// Expansions longer than this many characters always use `Paste` injection,
+/// whatever the configured mode, since per-character typing gets slow and
+/// can drop characters.
+pub const PASTE_LENGTH_THRESHOLD: usize = 200;
+
+/// Default delay after sending the paste shortcut before restoring the
+/// user's original clipboard contents, used unless `config.toml` overrides
+/// it via `global.paste_delay_ms`.
+pub const DEFAULT_PASTE_DELAY_MS: u64 = 150;
+
+/// How an expansion's text is delivered to the focused application.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InjectionMode {
+    /// Type the expansion character-by-character via `enigo.text`.
+    Type,
+    /// Stash the expansion on the clipboard and send a paste shortcut.
+    Paste,
+}
+
+/// Decides the effective injection mode for an expansion of the given
+/// length: an explicit `Paste` preference always pastes, and any expansion
+/// over [`PASTE_LENGTH_THRESHOLD`] pastes regardless of preference.
+pub fn resolve_mode(preference: InjectionMode, text_len: usize) -> InjectionMode {
+    if preference == InjectionMode::Paste || text_len > PASTE_LENGTH_THRESHOLD {
+        InjectionMode::Paste
+    } else {
+        InjectionMode::Type
+    }
+}
+
+/// Types `text` character-by-character.
+pub fn inject_by_typing(enigo: &mut Enigo, text: &str) {
+    enigo.text(text).ok();
+}
+
+/// Injects `text` by placing it on the clipboard and sending the platform
+/// paste shortcut, then waiting `restore_delay_ms` for the paste to be
+/// consumed before restoring whatever was on the clipboard before.
+///
+/// If the original clipboard couldn't be read back as text (e.g. it held an
+/// image), the restore step is skipped rather than clobbering it with an
+/// empty string.
+pub fn inject_by_paste(
+    enigo: &mut Enigo,
+    text: &str,
+    restore_delay_ms: u64,
+    clipboard: &dyn ClipboardProvider,
+) {
+    let original = clipboard.get();
+
+    if clipboard.set(text.to_string()).is_err() {
+        tracing::error!("Failed to set clipboard for paste injection");
+        return;
+    }
+
+    send_paste_shortcut(enigo);
+
+    // Give the target application time to consume the pasted clipboard
+    // contents before we overwrite them with the restore.
+    thread::sleep(Duration::from_millis(restore_delay_ms));
+
+    match original {
+        Ok(text) => {
+            clipboard.set(text).ok();
+        }
+        Err(_) => {
+            tracing::debug!("Original clipboard wasn't text; skipping restore");
+        }
+    }
+}
+
+/// Sends `n` backspace keystrokes, waiting `delay_ms` between each — some
+/// applications drop keystrokes sent faster than they can process them.
+pub fn backspace_n(enigo: &mut Enigo, n: usize, delay_ms: u64) {
+    for i in 0..n {
+        if i > 0 && delay_ms > 0 {
+            thread::sleep(Duration::from_millis(delay_ms));
+        }
+        enigo.key(Key::Backspace, Direction::Click).ok();
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn send_paste_shortcut(enigo: &mut Enigo) {
+    enigo.key(Key::Meta, Direction::Press).ok();
+    enigo.key(Key::Unicode('v'), Direction::Click).ok();
+    enigo.key(Key::Meta, Direction::Release).ok();
+}
+
+#[cfg(not(target_os = "macos"))]
+fn send_paste_shortcut(enigo: &mut Enigo) {
+    enigo.key(Key::Control, Direction::Press).ok();
+    enigo.key(Key::Unicode('v'), Direction::Click).ok();
+    enigo.key(Key::Control, Direction::Release).ok();
+}
@@ -1,15 +1,23 @@
 mod clipboard;
+mod config;
 mod logger;
 mod auto_expand;
+mod injection;
+mod palette;
+mod template;
 
-use clipboard::{read_clipboard, write_clipboard};
+use clipboard::{read_clipboard, read_primary, write_clipboard, write_primary, ClipboardProvider};
 use auto_expand::{AutoExpander, SnippetMap};
+use config::{Config, ConfigStore};
+use injection::InjectionMode;
 use std::sync::{Arc, Mutex};
 use tauri::State;
 
-struct AppState {
+pub(crate) struct AppState {
     auto_expander: Arc<Mutex<Option<AutoExpander>>>,
     snippet_map: SnippetMap,
+    pub(crate) clipboard: Arc<dyn ClipboardProvider>,
+    config: ConfigStore,
 }
 
 #[tauri::command]
@@ -36,18 +44,33 @@ fn disable_auto_expansion(state: State<AppState>) -> Result<(), String> {
     }
 }
 
+/// A single snippet sent from the frontend: an exact keyword match, or a
+/// regex trigger (optionally requiring a preceding word boundary).
+#[derive(serde::Deserialize)]
+struct SnippetInput {
+    keyword: String,
+    text: String,
+    #[serde(default)]
+    regex: bool,
+    #[serde(default)]
+    word: bool,
+}
+
 #[tauri::command]
-fn update_snippets_map(
-    keywords: Vec<String>,
-    texts: Vec<String>,
-    state: State<AppState>,
-) -> Result<(), String> {
-    if keywords.len() != texts.len() {
-        return Err("Keywords and texts length mismatch".to_string());
+fn update_snippets_map(snippets: Vec<SnippetInput>, state: State<AppState>) -> Result<(), String> {
+    let mut exact = Vec::new();
+    let mut regex = Vec::new();
+
+    for snippet in snippets {
+        if snippet.regex {
+            regex.push((snippet.keyword, snippet.text, snippet.word));
+        } else {
+            exact.push((snippet.keyword, snippet.text));
+        }
     }
 
-    let snippets: Vec<(String, String)> = keywords.into_iter().zip(texts).collect();
-    state.snippet_map.update(snippets);
+    state.snippet_map.update(exact);
+    state.snippet_map.update_regex(regex)?;
     tracing::info!("Snippet map updated from frontend");
     Ok(())
 }
@@ -62,6 +85,57 @@ fn is_auto_expansion_enabled(state: State<AppState>) -> Result<bool, String> {
     }
 }
 
+#[tauri::command]
+fn set_injection_mode(mode: String, state: State<AppState>) -> Result<(), String> {
+    let mode = match mode.as_str() {
+        "type" => InjectionMode::Type,
+        "paste" => InjectionMode::Paste,
+        other => return Err(format!("Unknown injection mode: {}", other)),
+    };
+
+    let expander = state.auto_expander.lock().unwrap();
+    if let Some(ref exp) = *expander {
+        exp.set_injection_mode(mode);
+        Ok(())
+    } else {
+        Err("Auto-expander not initialized".to_string())
+    }
+}
+
+#[tauri::command]
+fn search_snippets(query: String, state: State<AppState>) -> Result<Vec<(String, String)>, String> {
+    Ok(palette::search(&state.snippet_map, &query))
+}
+
+#[tauri::command]
+fn insert_snippet(keyword: String, state: State<AppState>) -> Result<(), String> {
+    let expander = state.auto_expander.lock().unwrap();
+    if let Some(ref exp) = *expander {
+        exp.insert_snippet(&keyword)
+    } else {
+        Err("Auto-expander not initialized".to_string())
+    }
+}
+
+#[tauri::command]
+fn load_config(state: State<AppState>) -> Result<Config, String> {
+    Ok(state.config.get())
+}
+
+#[tauri::command]
+fn reload_config(state: State<AppState>) -> Result<Config, String> {
+    state.config.reload()?;
+    Ok(state.config.get())
+}
+
+/// The error from the app's initial `config.toml` load, if it existed but
+/// couldn't be read or parsed (the app falls back to defaults rather than refusing to
+/// start, so the frontend has to ask for this explicitly to learn about it).
+#[tauri::command]
+fn config_load_error(state: State<AppState>) -> Option<String> {
+    state.config.load_error()
+}
+
 #[tauri::command]
 fn check_accessibility_permissions() -> bool {
     #[cfg(target_os = "macos")]
@@ -83,15 +157,35 @@ pub fn run() {
     logger::init();
     tracing::info!("Starting Snippet App");
 
+    let config = ConfigStore::load();
+    config.watch();
+
     let snippet_map = SnippetMap::new();
-    let auto_expander = AutoExpander::new(snippet_map.clone());
+    let clipboard = clipboard::detect_provider();
+    let auto_expander = AutoExpander::new(
+        snippet_map.clone(),
+        config.clone(),
+        Arc::clone(&clipboard),
+    );
+
+    if let Some(mode) = config.get().global.injection_mode.as_deref() {
+        match mode {
+            "type" => auto_expander.set_injection_mode(InjectionMode::Type),
+            "paste" => auto_expander.set_injection_mode(InjectionMode::Paste),
+            other => tracing::warn!("Ignoring unknown config injection_mode: {}", other),
+        }
+    }
 
     // Start keyboard listener
     auto_expander.clone().start();
 
+    let toggle_hotkey = config.get().global.toggle_hotkey.clone();
+
     let app_state = AppState {
         auto_expander: Arc::new(Mutex::new(Some(auto_expander))),
         snippet_map,
+        clipboard,
+        config,
     };
 
     tauri::Builder::default()
@@ -99,13 +193,25 @@ pub fn run() {
         .plugin(tauri_plugin_sql::Builder::default().build())
         .plugin(tauri_plugin_dialog::init())
         .manage(app_state)
+        .setup(move |app| {
+            palette::register_global_hotkey(app.handle().clone(), toggle_hotkey.clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             read_clipboard,
             write_clipboard,
+            read_primary,
+            write_primary,
             enable_auto_expansion,
             disable_auto_expansion,
             update_snippets_map,
             is_auto_expansion_enabled,
+            set_injection_mode,
+            search_snippets,
+            insert_snippet,
+            load_config,
+            reload_config,
+            config_load_error,
             check_accessibility_permissions
         ])
         .run(tauri::generate_context!())
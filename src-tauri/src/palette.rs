@@ -0,0 +1,206 @@
+use crate::auto_expand::SnippetMap;
+use global_hotkey::hotkey::{Code, HotKey, Modifiers};
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager};
+use tauri::{AppHandle, Emitter};
+
+/// Event emitted to the frontend when the palette hotkey is pressed, so it
+/// can open the search overlay.
+const TOGGLE_EVENT: &str = "palette:toggle";
+
+/// Hotkey spec used when `config.toml` doesn't set `global.toggle_hotkey`.
+const DEFAULT_HOTKEY_SPEC: &str = "ctrl+shift+space";
+
+/// Registers the global "open snippet picker" hotkey and forwards presses to
+/// the frontend as [`TOGGLE_EVENT`]. `toggle_hotkey` is a `+`-separated spec
+/// like `"ctrl+shift+space"` (see `parse_hotkey`), taken from
+/// `config.toml`'s `global.toggle_hotkey`; falls back to
+/// [`DEFAULT_HOTKEY_SPEC`] if unset or unparseable. Mirrors the `rdev`
+/// listener thread in `auto_expand::AutoExpander::start`: the manager has to
+/// stay alive for the registration to hold, so it's moved into the spawned
+/// thread rather than dropped at the end of this function.
+pub fn register_global_hotkey(app_handle: AppHandle, toggle_hotkey: Option<String>) {
+    std::thread::spawn(move || {
+        let manager = match GlobalHotKeyManager::new() {
+            Ok(manager) => manager,
+            Err(e) => {
+                tracing::error!("Failed to create global hotkey manager: {:?}", e);
+                return;
+            }
+        };
+
+        let requested = toggle_hotkey.as_deref().unwrap_or(DEFAULT_HOTKEY_SPEC);
+        let (spec, hotkey) = match parse_hotkey(requested) {
+            Ok(hotkey) => (requested, hotkey),
+            Err(e) => {
+                tracing::warn!(
+                    "{}; falling back to default hotkey ({})",
+                    e,
+                    DEFAULT_HOTKEY_SPEC
+                );
+                let hotkey =
+                    parse_hotkey(DEFAULT_HOTKEY_SPEC).expect("default hotkey spec must parse");
+                (DEFAULT_HOTKEY_SPEC, hotkey)
+            }
+        };
+
+        if let Err(e) = manager.register(hotkey) {
+            tracing::error!("Failed to register palette hotkey: {:?}", e);
+            return;
+        }
+
+        tracing::info!("Registered snippet picker hotkey ({})", spec);
+
+        let receiver = GlobalHotKeyEvent::receiver();
+        while let Ok(event) = receiver.recv() {
+            if event.id == hotkey.id() {
+                if let Err(e) = app_handle.emit(TOGGLE_EVENT, ()) {
+                    tracing::error!("Failed to emit {}: {:?}", TOGGLE_EVENT, e);
+                }
+            }
+        }
+    });
+}
+
+/// Parses a `+`-separated hotkey spec like `"ctrl+shift+space"` into a
+/// [`HotKey`]. Modifier names are case-insensitive; exactly one non-modifier
+/// key is required.
+fn parse_hotkey(spec: &str) -> Result<HotKey, String> {
+    let mut modifiers = Modifiers::empty();
+    let mut code = None;
+
+    for part in spec.split('+') {
+        let part = part.trim();
+        if part.is_empty() {
+            continue;
+        }
+
+        match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => modifiers |= Modifiers::CONTROL,
+            "shift" => modifiers |= Modifiers::SHIFT,
+            "alt" | "option" => modifiers |= Modifiers::ALT,
+            "super" | "cmd" | "meta" | "win" => modifiers |= Modifiers::SUPER,
+            other => {
+                if code.is_some() {
+                    return Err(format!("More than one key in hotkey spec '{}'", spec));
+                }
+                code = Some(parse_code(other)?);
+            }
+        }
+    }
+
+    let code = code.ok_or_else(|| format!("No key in hotkey spec '{}'", spec))?;
+    Ok(HotKey::new(Some(modifiers), code))
+}
+
+/// Maps a single key name from a hotkey spec to its [`Code`].
+fn parse_code(key: &str) -> Result<Code, String> {
+    match key {
+        "space" => Ok(Code::Space),
+        "enter" | "return" => Ok(Code::Enter),
+        "tab" => Ok(Code::Tab),
+        "escape" | "esc" => Ok(Code::Escape),
+        "a" => Ok(Code::KeyA),
+        "b" => Ok(Code::KeyB),
+        "c" => Ok(Code::KeyC),
+        "d" => Ok(Code::KeyD),
+        "e" => Ok(Code::KeyE),
+        "f" => Ok(Code::KeyF),
+        "g" => Ok(Code::KeyG),
+        "h" => Ok(Code::KeyH),
+        "i" => Ok(Code::KeyI),
+        "j" => Ok(Code::KeyJ),
+        "k" => Ok(Code::KeyK),
+        "l" => Ok(Code::KeyL),
+        "m" => Ok(Code::KeyM),
+        "n" => Ok(Code::KeyN),
+        "o" => Ok(Code::KeyO),
+        "p" => Ok(Code::KeyP),
+        "q" => Ok(Code::KeyQ),
+        "r" => Ok(Code::KeyR),
+        "s" => Ok(Code::KeyS),
+        "t" => Ok(Code::KeyT),
+        "u" => Ok(Code::KeyU),
+        "v" => Ok(Code::KeyV),
+        "w" => Ok(Code::KeyW),
+        "x" => Ok(Code::KeyX),
+        "y" => Ok(Code::KeyY),
+        "z" => Ok(Code::KeyZ),
+        "0" => Ok(Code::Digit0),
+        "1" => Ok(Code::Digit1),
+        "2" => Ok(Code::Digit2),
+        "3" => Ok(Code::Digit3),
+        "4" => Ok(Code::Digit4),
+        "5" => Ok(Code::Digit5),
+        "6" => Ok(Code::Digit6),
+        "7" => Ok(Code::Digit7),
+        "8" => Ok(Code::Digit8),
+        "9" => Ok(Code::Digit9),
+        other => Err(format!("Unknown hotkey key '{}'", other)),
+    }
+}
+
+/// Scores `candidate` against `query` as a fuzzy subsequence match: every
+/// character of `query` must appear in order in `candidate`, earning a bonus
+/// for a match at the very start of `candidate` and for each contiguous run;
+/// where a match starts past the first character does not change its
+/// score. Returns
+/// `None` if `query` isn't a subsequence of `candidate` at all.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let candidate_lower = candidate.to_lowercase();
+    let query_lower = query.to_lowercase();
+    let mut query_chars = query_lower.chars().peekable();
+
+    let mut score = 0i64;
+    let mut previous_match_index: Option<usize> = None;
+
+    for (index, ch) in candidate_lower.chars().enumerate() {
+        let Some(&target) = query_chars.peek() else {
+            break;
+        };
+
+        if ch == target {
+            query_chars.next();
+
+            score += if index == 0 { 10 } else { 1 };
+            if previous_match_index == Some(index.wrapping_sub(1)) {
+                score += 5; // bonus for contiguous runs
+            }
+            previous_match_index = Some(index);
+        }
+    }
+
+    if query_chars.peek().is_some() {
+        None // not every query char was found, in order
+    } else {
+        Some(score)
+    }
+}
+
+/// Fuzzy-searches `snippet_map`'s keywords and bodies for `query`, returning
+/// `(keyword, body)` pairs ranked best match first. A keyword match scores
+/// higher than a body-only match at the same position/contiguity.
+pub fn search(snippet_map: &SnippetMap, query: &str) -> Vec<(String, String)> {
+    const KEYWORD_MATCH_BONUS: i64 = 1000;
+
+    let mut scored: Vec<(i64, String, String)> = snippet_map
+        .entries()
+        .into_iter()
+        .filter_map(|(keyword, body)| {
+            let keyword_score = fuzzy_score(query, &keyword).map(|s| s + KEYWORD_MATCH_BONUS);
+            let body_score = fuzzy_score(query, &body);
+
+            keyword_score
+                .into_iter()
+                .chain(body_score)
+                .max()
+                .map(|score| (score, keyword, body))
+        })
+        .collect();
+
+    scored.sort_by(|a, b| b.0.cmp(&a.0));
+    scored.into_iter().map(|(_, k, v)| (k, v)).collect()
+}
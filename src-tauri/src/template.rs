@@ -0,0 +1,142 @@
+use crate::auto_expand::SnippetMap;
+use crate::clipboard::ClipboardProvider;
+use std::sync::Arc;
+
+/// Maximum nesting depth for `{{snippet:name}}` references, to guard against
+/// snippets that (accidentally or not) reference each other in a cycle.
+const MAX_SNIPPET_DEPTH: u8 = 8;
+
+/// Context available while rendering a snippet template.
+#[derive(Clone)]
+pub struct ExpandContext {
+    pub snippet_map: SnippetMap,
+    /// Regex capture groups from the trigger that matched, if any, indexed
+    /// from zero (i.e. `captures[0]` is `{{$1}}`). Empty for exact-keyword
+    /// triggers, which have no capture groups.
+    pub captures: Vec<String>,
+    /// The same detected backend used for paste injection and the
+    /// `read_clipboard`/`write_clipboard` commands, so `{{clipboard}}`
+    /// resolves correctly on Wayland too instead of only through `arboard`.
+    pub clipboard: Arc<dyn ClipboardProvider>,
+}
+
+/// Result of rendering a template: the text to type, plus where the caret
+/// should end up if the template contained a `{{cursor}}` marker.
+pub struct RenderedExpansion {
+    pub text: String,
+    /// Number of characters typed *after* the `{{cursor}}` marker, i.e. how
+    /// many left-arrow presses are needed to move the caret back to it.
+    pub cursor_offset: Option<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment<'a> {
+    Literal(&'a str),
+    Date(&'a str),
+    Clipboard,
+    Cursor,
+    Snippet(&'a str),
+    /// `{{$N}}`: the N-th (1-indexed) regex capture group of the trigger
+    /// that matched.
+    Capture(usize),
+}
+
+/// Splits a template into literal text and `{{...}}` token segments.
+fn tokenize(template: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            segments.push(Segment::Literal(&rest[..start]));
+        }
+
+        let after_open = &rest[start + 2..];
+        let Some(end) = after_open.find("}}") else {
+            // Unterminated `{{`: treat the remainder as literal text.
+            segments.push(Segment::Literal(&rest[start..]));
+            rest = "";
+            break;
+        };
+
+        let token = &after_open[..end];
+        segments.push(match token.split_once(':') {
+            Some(("date", fmt)) => Segment::Date(fmt),
+            Some(("snippet", name)) => Segment::Snippet(name),
+            _ if token == "clipboard" => Segment::Clipboard,
+            _ if token == "cursor" => Segment::Cursor,
+            _ if is_capture_token(token) => Segment::Capture(token[1..].parse().unwrap()),
+            _ => Segment::Literal(&rest[start..start + 2 + end + 2]),
+        });
+
+        rest = &after_open[end + 2..];
+    }
+
+    if !rest.is_empty() {
+        segments.push(Segment::Literal(rest));
+    }
+
+    segments
+}
+
+/// Whether `token` is a `$N` capture-group reference, e.g. `$1`. Capture
+/// groups are 1-indexed, so `$0` doesn't qualify: treating it as a literal
+/// instead avoids `n - 1` underflowing when a user reaches for `$0`
+/// (a "whole match" placeholder in some regex dialects, but not this one).
+fn is_capture_token(token: &str) -> bool {
+    token.len() > 1
+        && token.starts_with('$')
+        && token[1..].chars().all(|c| c.is_ascii_digit())
+        && token[1..].parse::<usize>().map(|n| n > 0).unwrap_or(false)
+}
+
+/// Renders a snippet template, resolving `{{date:...}}`, `{{clipboard}}`,
+/// `{{cursor}}` and `{{snippet:name}}` tokens.
+pub fn render(template: &str, ctx: &ExpandContext) -> RenderedExpansion {
+    render_depth(template, ctx, 0)
+}
+
+fn render_depth(template: &str, ctx: &ExpandContext, depth: u8) -> RenderedExpansion {
+    let mut text = String::new();
+    let mut cursor_offset = None;
+
+    for segment in tokenize(template) {
+        match segment {
+            Segment::Literal(s) => text.push_str(s),
+            Segment::Date(fmt) => text.push_str(&chrono::Local::now().format(fmt).to_string()),
+            Segment::Clipboard => {
+                if let Ok(clipboard_text) = ctx.clipboard.get() {
+                    text.push_str(&clipboard_text);
+                }
+            }
+            Segment::Cursor => {
+                cursor_offset = Some(text.chars().count());
+            }
+            Segment::Snippet(name) => {
+                if depth >= MAX_SNIPPET_DEPTH {
+                    tracing::warn!("Snippet nesting too deep, skipping '{}'", name);
+                    continue;
+                }
+
+                if let Some(nested) = ctx.snippet_map.get(name) {
+                    let rendered = render_depth(&nested, ctx, depth + 1);
+                    text.push_str(&rendered.text);
+                }
+            }
+            Segment::Capture(n) => {
+                if let Some(group) = ctx.captures.get(n - 1) {
+                    text.push_str(group);
+                }
+            }
+        }
+    }
+
+    // `cursor_offset` currently holds the marker's position; convert it into
+    // the number of characters typed *after* it.
+    let cursor_offset = cursor_offset.map(|pos| text.chars().count() - pos);
+
+    RenderedExpansion {
+        text,
+        cursor_offset,
+    }
+}